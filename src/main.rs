@@ -1,5 +1,84 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::time::Instant;
 
+/// A total-ordering wrapper around `f64` so similarity scores can live in a
+/// `BinaryHeap` without `partial_cmp` derailing on `NaN`. `NaN` is treated as
+/// the smallest possible score, so it never displaces a real result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OrderedScore(f64);
+
+impl OrderedScore {
+    fn new(value: f64) -> Self {
+        if value.is_nan() {
+            OrderedScore(f64::NEG_INFINITY)
+        } else {
+            OrderedScore(value)
+        }
+    }
+}
+
+impl Eq for OrderedScore {}
+
+impl PartialOrd for OrderedScore {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedScore {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Entry held in the bounded top-k heap: a score plus the id needed to look
+/// the full `SimilarityArrayObject` back up once the heap has settled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HeapEntry {
+    score: OrderedScore,
+    id: i32,
+}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // On equal scores the lower id counts as better, matching the final
+        // ranking's tie-break, so the heap evicts the higher id first.
+        self.score.cmp(&other.score).then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+/// Distance/similarity metric selectable per query. `Cosine` and
+/// `DotProduct` are "higher is more similar"; `SquaredEuclidean` is "lower is
+/// more similar" (it's a distance, not a similarity).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    Cosine,
+    SquaredEuclidean,
+    DotProduct,
+}
+
+impl Metric {
+    fn higher_is_more_similar(&self) -> bool {
+        match self {
+            Metric::Cosine | Metric::DotProduct => true,
+            Metric::SquaredEuclidean => false,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Item {
     rating: Vec<i32>,
@@ -14,10 +93,31 @@ struct Key {
     weight: i32,
 }
 
+/// A node in a random-projection tree: an internal `Split` partitions its
+/// items by the median projection onto a random unit vector, and a `Leaf`
+/// holds the items that make it down to a small enough bucket.
+#[derive(Debug)]
+enum ProjectionNode {
+    Leaf {
+        ids: Vec<i32>,
+    },
+    Split {
+        projection: Vec<f64>,
+        median: f64,
+        left: Box<ProjectionNode>,
+        right: Box<ProjectionNode>,
+    },
+}
+
 #[derive(Debug)]
 struct Similarity {
     keys: Vec<Key>,
     items: Vec<Item>,
+    index: Option<Vec<ProjectionNode>>,
+    // Ratings nudged by `record_match`, keyed by item id. Kept separate from
+    // `Item.rating` so the original integer ratings are never clobbered; any
+    // id without an entry here just uses its original rating.
+    learned_ratings: HashMap<i32, Vec<f64>>,
 }
 
 #[derive(Debug, Clone)]
@@ -37,28 +137,96 @@ impl Similarity {
         self.items.push(item);
     }
 
-    pub fn dot_product(&self, item_1: &Item, item_2: &Item) -> i32 {
+    /// The rating vector to actually score with: the learned rating if
+    /// `record_match` has adjusted this item, otherwise its original rating.
+    /// Falls back to the passed-in `Item`'s own rating rather than
+    /// re-looking it up in `self.items`, so transient items that aren't
+    /// (and shouldn't be) stored there — like `analogy`'s synthesized
+    /// target — can still be scored.
+    fn effective_rating(&self, item: &Item) -> Vec<f64> {
+        if let Some(rating) = self.learned_ratings.get(&item.id) {
+            return rating.clone();
+        }
+
+        return item.rating.iter().map(|&value| f64::from(value)).collect();
+    }
+
+    fn rating_or_default(&self, item_id: i32) -> Option<Vec<f64>> {
+        if let Some(rating) = self.learned_ratings.get(&item_id) {
+            return Some(rating.clone());
+        }
+
+        let item = self.items.iter().find(|item| item.id == item_id)?;
+
+        return Some(item.rating.iter().map(|&value| f64::from(value)).collect());
+    }
+
+    pub fn dot_product(&self, item_1: &Item, item_2: &Item) -> f64 {
         if item_1.rating.len() != item_2.rating.len() {
             panic!("All Items ratings length must be equal.")
         };
 
-        let dot_product = item_1
-            .rating
+        let rating_1 = self.effective_rating(item_1);
+        let rating_2 = self.effective_rating(item_2);
+
+        return rating_1
             .iter()
-            .zip(item_2.rating.iter())
+            .zip(rating_2.iter())
             .map(|(&x, &y)| x * y)
             .sum();
-
-        return dot_product;
     }
 
     pub fn magnitude(&self, item: &Item) -> f64 {
-        let values_squared_added: i32 = item.rating.iter().map(|rating| rating * rating).sum();
+        let rating = self.effective_rating(item);
+        let values_squared_added: f64 = rating.iter().map(|value| value * value).sum();
+
+        return values_squared_added.sqrt();
+    }
+
+    pub fn weighted_dot_product(&self, item_1: &Item, item_2: &Item) -> f64 {
+        if item_1.rating.len() != item_2.rating.len() {
+            panic!("All Items ratings length must be equal.")
+        };
+
+        if self.keys.len() != item_1.rating.len() {
+            panic!("Keys length must match Items rating length.")
+        };
+
+        let rating_1 = self.effective_rating(item_1);
+        let rating_2 = self.effective_rating(item_2);
 
-        return f64::from(values_squared_added).sqrt();
+        return self
+            .keys
+            .iter()
+            .zip(rating_1.iter().zip(rating_2.iter()))
+            .map(|(key, (&x, &y))| f64::from(key.weight) * x * y)
+            .sum();
+    }
+
+    pub fn weighted_magnitude(&self, item: &Item) -> f64 {
+        if self.keys.len() != item.rating.len() {
+            panic!("Keys length must match Items rating length.")
+        };
+
+        let rating = self.effective_rating(item);
+
+        let values_squared_added: f64 = self
+            .keys
+            .iter()
+            .zip(rating.iter())
+            .map(|(key, &x)| f64::from(key.weight) * x * x)
+            .sum();
+
+        return values_squared_added.sqrt();
     }
 
     pub fn cosine_similarity(&self, item_1: &Item, item_2: &Item) -> f64 {
+        // When keys are defined, weight each dimension positionally instead of
+        // treating all ratings as equally important.
+        if !self.keys.is_empty() {
+            return self.weighted_cosine_similarity(item_1, item_2);
+        }
+
         let dot_product = self.dot_product(item_1, item_2);
         let magnitude_1 = self.magnitude(item_1);
         let magnitude_2 = self.magnitude(item_2);
@@ -67,48 +235,352 @@ impl Similarity {
             return 0.0;
         };
 
-        return f64::from(dot_product) / (magnitude_1 * magnitude_2);
+        return dot_product / (magnitude_1 * magnitude_2);
     }
 
-    pub fn get_similar(&self, item_id: i32) -> Vec<SimilarityArrayObject> {
-        let item = self
-            .items
+    pub fn weighted_cosine_similarity(&self, item_1: &Item, item_2: &Item) -> f64 {
+        let dot_product = self.weighted_dot_product(item_1, item_2);
+        let magnitude_1 = self.weighted_magnitude(item_1);
+        let magnitude_2 = self.weighted_magnitude(item_2);
+
+        if magnitude_1 == 0.0 || magnitude_2 == 0.0 {
+            return 0.0;
+        };
+
+        return dot_product / (magnitude_1 * magnitude_2);
+    }
+
+    pub fn squared_euclidean(&self, item_1: &Item, item_2: &Item) -> f64 {
+        if item_1.rating.len() != item_2.rating.len() {
+            panic!("All Items ratings length must be equal.")
+        };
+
+        let rating_1 = self.effective_rating(item_1);
+        let rating_2 = self.effective_rating(item_2);
+
+        return rating_1
             .iter()
-            .find(|item| item.id == item_id)
-            .expect("Couldn't find item");
+            .zip(rating_2.iter())
+            .map(|(&x, &y)| {
+                let diff = x - y;
+                diff * diff
+            })
+            .sum();
+    }
+
+    pub fn score(&self, metric: Metric, item_1: &Item, item_2: &Item) -> f64 {
+        match metric {
+            Metric::Cosine => self.cosine_similarity(item_1, item_2),
+            Metric::SquaredEuclidean => self.squared_euclidean(item_1, item_2),
+            Metric::DotProduct => self.dot_product(item_1, item_2),
+        }
+    }
+
+    fn build_tree(&self, ids: &[i32], leaf_size: usize, rng: &mut impl Rng) -> ProjectionNode {
+        if ids.len() <= leaf_size {
+            return ProjectionNode::Leaf { ids: ids.to_vec() };
+        }
+
+        let dims = self.items[0].rating.len();
+        let mut projection: Vec<f64> = (0..dims).map(|_| rng.gen_range(-1.0..1.0)).collect();
+        let norm = projection.iter().map(|value| value * value).sum::<f64>().sqrt();
+        if norm > 0.0 {
+            projection.iter_mut().for_each(|value| *value /= norm);
+        }
 
-        let mut all_items_calculated: Vec<SimilarityArrayObject> = vec![];
+        let mut projected: Vec<(i32, f64)> = ids
+            .iter()
+            .map(|&id| {
+                let item = self
+                    .items
+                    .iter()
+                    .find(|item| item.id == id)
+                    .expect("indexed item missing from item set");
+                let rating = self.effective_rating(item);
+                let value: f64 = rating
+                    .iter()
+                    .zip(projection.iter())
+                    .map(|(&r, &p)| r * p)
+                    .sum();
+                (id, value)
+            })
+            .collect();
+
+        projected.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+
+        let mid = projected.len() / 2;
+        let median = projected[mid].1;
+        let left_ids: Vec<i32> = projected[..mid].iter().map(|(id, _)| *id).collect();
+        let right_ids: Vec<i32> = projected[mid..].iter().map(|(id, _)| *id).collect();
+
+        return ProjectionNode::Split {
+            left: Box::new(self.build_tree(&left_ids, leaf_size, rng)),
+            right: Box::new(self.build_tree(&right_ids, leaf_size, rng)),
+            median,
+            projection,
+        };
+    }
+
+    /// Build a forest of random-projection trees so queries can restrict
+    /// their scan to a small candidate set instead of touching every item.
+    /// A larger forest trades build time and memory for better recall.
+    /// `seed` makes the forest (and therefore query results once one is
+    /// built) reproducible across runs instead of depending on thread-local
+    /// entropy.
+    pub fn build_index(&mut self, trees: usize, leaf_size: usize, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let all_ids: Vec<i32> = self.items.iter().map(|item| item.id).collect();
+
+        let forest = (0..trees)
+            .map(|_| self.build_tree(&all_ids, leaf_size, &mut rng))
+            .collect();
+
+        self.index = Some(forest);
+    }
+
+    /// Descend every tree in the forest following `target`'s projection and
+    /// union the candidate leaves. Falls back to every item id when no index
+    /// has been built.
+    fn candidate_ids(&self, target: &Item) -> HashSet<i32> {
+        let forest = match &self.index {
+            Some(forest) => forest,
+            None => return self.items.iter().map(|item| item.id).collect(),
+        };
+
+        let target_rating = self.effective_rating(target);
+        let mut candidates: HashSet<i32> = HashSet::new();
+
+        for tree in forest {
+            let mut node = tree;
+
+            loop {
+                match node {
+                    ProjectionNode::Leaf { ids } => {
+                        candidates.extend(ids.iter().copied());
+                        break;
+                    }
+                    ProjectionNode::Split {
+                        projection,
+                        median,
+                        left,
+                        right,
+                    } => {
+                        let value: f64 = target_rating
+                            .iter()
+                            .zip(projection.iter())
+                            .map(|(&r, &p)| r * p)
+                            .sum();
+
+                        node = if value <= *median { left } else { right };
+                    }
+                }
+            }
+        }
+
+        return candidates;
+    }
+
+    /// Bounded min-heap top-k of `self.items` by `metric` against `target`,
+    /// skipping any id in `exclude`. `want_most_similar` flips the ordering so
+    /// the same path serves both `get_similar`/`analogy` (closest) and
+    /// `get_dissimilar` (farthest).
+    fn rank(
+        &self,
+        target: &Item,
+        exclude: &[i32],
+        limit: usize,
+        metric: Metric,
+        want_most_similar: bool,
+    ) -> Vec<SimilarityArrayObject<'_>> {
+        // "Goodness" always means "bigger belongs higher in the heap", so the
+        // same bounded-heap code works regardless of whether the metric is a
+        // similarity (bigger = closer) or a distance (smaller = closer), and
+        // regardless of whether we want the closest or the farthest items.
+        let goodness = |score: f64| -> f64 {
+            if metric.higher_is_more_similar() == want_most_similar {
+                score
+            } else {
+                -score
+            }
+        };
+
+        // The index is only an approximation of cosine nearest-neighbors, so
+        // only consult it for that exact case; every other query still does
+        // the exact linear scan.
+        let candidates = if metric == Metric::Cosine && want_most_similar && self.index.is_some() {
+            Some(self.candidate_ids(target))
+        } else {
+            None
+        };
+
+        // Bounded min-heap of size `limit`: keep only the best candidates seen
+        // so far instead of sorting the full item set, which is O(n log k)
+        // rather than O(n log n).
+        let mut heap: BinaryHeap<Reverse<HeapEntry>> = BinaryHeap::with_capacity(limit + 1);
+        let mut by_id: HashMap<i32, SimilarityArrayObject> = HashMap::new();
 
         for current_item in self.items.iter() {
-            if current_item.id == item.id {
+            if exclude.contains(&current_item.id) {
                 continue;
             }
 
-            let value = self.cosine_similarity(item, &current_item);
+            if candidates.as_ref().is_some_and(|ids| !ids.contains(&current_item.id)) {
+                continue;
+            }
+
+            let similarity = self.score(metric, target, current_item);
 
-            all_items_calculated.push(SimilarityArrayObject {
+            heap.push(Reverse(HeapEntry {
+                score: OrderedScore::new(goodness(similarity)),
                 id: current_item.id,
-                similarity: value,
-                against: current_item,
-                title: &current_item.title,
-            });
+            }));
+            by_id.insert(
+                current_item.id,
+                SimilarityArrayObject {
+                    id: current_item.id,
+                    similarity,
+                    against: current_item,
+                    title: &current_item.title,
+                },
+            );
+
+            if let Some(Reverse(smallest)) = (heap.len() > limit).then(|| heap.pop()).flatten() {
+                by_id.remove(&smallest.id);
+            }
         }
 
-        // Sort the similarities in descending order
-        all_items_calculated.sort_by(|a, b| {
-            b.similarity
-                .partial_cmp(&a.similarity)
-                .unwrap_or(std::cmp::Ordering::Equal)
+        let mut ranked: Vec<SimilarityArrayObject> = heap
+            .into_iter()
+            .filter_map(|Reverse(entry)| by_id.remove(&entry.id))
+            .collect();
+
+        // Drain into a descending-by-goodness Vec, breaking ties by id.
+        ranked.sort_by(|a, b| {
+            OrderedScore::new(goodness(b.similarity))
+                .cmp(&OrderedScore::new(goodness(a.similarity)))
+                .then_with(|| a.id.cmp(&b.id))
         });
 
-        // Take the top 5 items
-        let top_5_similar = all_items_calculated
+        return ranked;
+    }
+
+    pub fn get_similar(&self, item_id: i32, limit: usize, metric: Metric) -> Vec<SimilarityArrayObject<'_>> {
+        let item = self
+            .items
+            .iter()
+            .find(|item| item.id == item_id)
+            .expect("Couldn't find item");
+
+        return self.rank(item, &[item.id], limit, metric, true);
+    }
+
+    /// The inverse of `get_similar`: the farthest items by the chosen metric
+    /// instead of the nearest.
+    pub fn get_dissimilar(
+        &self,
+        item_id: i32,
+        limit: usize,
+        metric: Metric,
+    ) -> Vec<SimilarityArrayObject<'_>> {
+        let item = self
+            .items
             .iter()
-            .take(5)
-            .cloned()
-            .collect::<Vec<_>>();
+            .find(|item| item.id == item_id)
+            .expect("Couldn't find item");
 
-        return top_5_similar;
+        return self.rank(item, &[item.id], limit, metric, false);
+    }
+
+    /// "A is to B as C is to ?" — computes the target rating vector
+    /// `rating(B) - rating(A) + rating(C)` and ranks every other item by
+    /// cosine similarity to that synthesized vector.
+    pub fn analogy(
+        &self,
+        a_id: i32,
+        b_id: i32,
+        c_id: i32,
+        limit: usize,
+    ) -> Vec<SimilarityArrayObject<'_>> {
+        let item_a = self
+            .items
+            .iter()
+            .find(|item| item.id == a_id)
+            .expect("Couldn't find item A");
+        let item_b = self
+            .items
+            .iter()
+            .find(|item| item.id == b_id)
+            .expect("Couldn't find item B");
+        let item_c = self
+            .items
+            .iter()
+            .find(|item| item.id == c_id)
+            .expect("Couldn't find item C");
+
+        if item_a.rating.len() != item_b.rating.len() || item_b.rating.len() != item_c.rating.len()
+        {
+            panic!("All Items ratings length must be equal.")
+        };
+
+        // Use each item's effective (possibly `record_match`-adjusted) rating
+        // rather than its raw one, so the analogy reflects what the engine
+        // has learned, same as every other query. `Item.rating` is an i32
+        // vector, so the float result is rounded back to build the
+        // transient target.
+        let rating_a = self.effective_rating(item_a);
+        let rating_b = self.effective_rating(item_b);
+        let rating_c = self.effective_rating(item_c);
+
+        let target_rating: Vec<i32> = rating_b
+            .iter()
+            .zip(rating_a.iter())
+            .zip(rating_c.iter())
+            .map(|((&b, &a), &c)| (b - a + c).round() as i32)
+            .collect();
+
+        let target = Item {
+            id: -1,
+            title: String::from("(analogy target)"),
+            description: String::new(),
+            rating: target_rating,
+        };
+
+        return self.rank(&target, &[a_id, b_id, c_id], limit, Metric::Cosine, true);
+    }
+
+    /// Nudge `winner_id` and `loser_id`'s `rating[dimension]` towards/away
+    /// from each other using an Elo-style update, learning a preference
+    /// weighted rating from head-to-head feedback instead of requiring a
+    /// fixed vector up front. `get_similar` and friends pick these adapted
+    /// ratings up automatically via `effective_rating`. Does nothing if
+    /// either id is unknown or `dimension` is out of range for either
+    /// item's rating — callers pass ids from user interactions, so a bad
+    /// id is a no-op rather than a panic.
+    pub fn record_match(&mut self, winner_id: i32, loser_id: i32, dimension: usize, k: f64) {
+        let mut winner_rating = match self.rating_or_default(winner_id) {
+            Some(rating) => rating,
+            None => return,
+        };
+        let mut loser_rating = match self.rating_or_default(loser_id) {
+            Some(rating) => rating,
+            None => return,
+        };
+
+        if dimension >= winner_rating.len() || dimension >= loser_rating.len() {
+            return;
+        }
+
+        let r_winner = winner_rating[dimension];
+        let r_loser = loser_rating[dimension];
+
+        let expected_winner = 1.0 / (1.0 + 10f64.powf((r_loser - r_winner) / 400.0));
+        let expected_loser = 1.0 - expected_winner;
+
+        winner_rating[dimension] += k * (1.0 - expected_winner);
+        loser_rating[dimension] += k * (0.0 - expected_loser);
+
+        self.learned_ratings.insert(winner_id, winner_rating);
+        self.learned_ratings.insert(loser_id, loser_rating);
     }
 }
 
@@ -116,6 +588,8 @@ fn init_test_similarity_engine() -> Similarity {
     let mut engine = Similarity {
         keys: vec![],
         items: vec![],
+        index: None,
+        learned_ratings: HashMap::new(),
     };
 
     engine.create_key(Key {
@@ -202,21 +676,23 @@ fn init_test_similarity_engine() -> Similarity {
 }
 
 fn main() {
-    let engine = init_test_similarity_engine();
+    let mut engine = init_test_similarity_engine();
 
     let id = 5;
 
-    let id_item = engine
+    let id_item_title = engine
         .items
         .iter()
         .find(|item| item.id == id)
-        .expect("Invalid id");
+        .expect("Invalid id")
+        .title
+        .clone();
 
-    println!("Finding top 5 for {}...\n", id_item.title);
+    println!("Finding top 5 for {}...\n", id_item_title);
 
     let start_time = Instant::now();
 
-    let similar = engine.get_similar(id);
+    let similar = engine.get_similar(id, 5, Metric::Cosine);
 
     let end_time = Instant::now();
     let elapsed_time = end_time - start_time;
@@ -231,4 +707,127 @@ fn main() {
     });
 
     println!("Execution time: {:?}", elapsed_time);
+
+    println!("\nLeast similar to {}...\n", id_item_title);
+
+    for item in engine.get_dissimilar(id, 3, Metric::SquaredEuclidean) {
+        println!("Item: {}\nScore: {}\n", item.title, item.similarity);
+    }
+
+    println!("\nBrooklyn 99 is to action as {} is to...\n", id_item_title);
+
+    for item in engine.analogy(1, 4, 5, 3) {
+        println!("Item: {}\nScore: {}\n", item.title, item.similarity);
+    }
+
+    engine.build_index(6, 2, 42);
+    println!("\nTop 3 for {} (via the ANN index)...\n", id_item_title);
+
+    for item in engine.get_similar(id, 3, Metric::Cosine) {
+        println!("Item: {}\nScore: {}\n", item.title, item.similarity);
+    }
+
+    for _ in 0..20 {
+        engine.record_match(4, 8, 1, 32.0);
+    }
+    println!("\nTop 3 for {} (after learning a preference)...\n", id_item_title);
+
+    for item in engine.get_similar(id, 3, Metric::DotProduct) {
+        println!("Item: {}\nScore: {}\n", item.title, item.similarity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analogy_returns_limit_results_excluding_inputs() {
+        let engine = init_test_similarity_engine();
+
+        // Brooklyn 99 (comedy) : my custom action movie (action) :: John Wick : ?
+        let result = engine.analogy(1, 4, 5, 3);
+
+        assert_eq!(result.len(), 3);
+        assert!(result.iter().all(|r| r.id != 1 && r.id != 4 && r.id != 5));
+    }
+
+    #[test]
+    fn get_dissimilar_is_the_inverse_of_get_similar() {
+        let engine = init_test_similarity_engine();
+
+        let most_similar = engine.get_similar(5, 1, Metric::Cosine);
+        let most_dissimilar = engine.get_dissimilar(5, 1, Metric::Cosine);
+
+        assert_eq!(most_similar.len(), 1);
+        assert_eq!(most_dissimilar.len(), 1);
+        assert_ne!(most_similar[0].id, most_dissimilar[0].id);
+        assert!(most_similar[0].similarity >= most_dissimilar[0].similarity);
+    }
+
+    #[test]
+    fn get_similar_supports_every_metric() {
+        let engine = init_test_similarity_engine();
+
+        for metric in [Metric::Cosine, Metric::SquaredEuclidean, Metric::DotProduct] {
+            let result = engine.get_similar(5, 3, metric);
+            assert_eq!(result.len(), 3);
+        }
+    }
+
+    #[test]
+    fn get_similar_uses_the_index_transparently_when_built() {
+        let mut engine = init_test_similarity_engine();
+        engine.build_index(6, 2, 42);
+
+        // The index only approximates nearest neighbors, so a forest built
+        // over this tiny item set isn't guaranteed to surface `limit`
+        // candidates — assert it stays within bounds and sane instead of an
+        // exact count.
+        let result = engine.get_similar(5, 3, Metric::Cosine);
+
+        assert!(!result.is_empty());
+        assert!(result.len() <= 3);
+        assert!(result.iter().all(|r| r.id != 5));
+    }
+
+    #[test]
+    fn record_match_adjusts_get_similar() {
+        let mut engine = init_test_similarity_engine();
+
+        let before: Vec<f64> = engine
+            .get_similar(5, 3, Metric::Cosine)
+            .iter()
+            .map(|r| r.similarity)
+            .collect();
+
+        for _ in 0..20 {
+            engine.record_match(4, 8, 1, 32.0);
+        }
+
+        let after: Vec<f64> = engine
+            .get_similar(5, 3, Metric::Cosine)
+            .iter()
+            .map(|r| r.similarity)
+            .collect();
+
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn record_match_ignores_out_of_range_dimension() {
+        let mut engine = init_test_similarity_engine();
+
+        // Should not panic: dimension 99 is out of range for every item.
+        engine.record_match(4, 8, 99, 32.0);
+    }
+
+    #[test]
+    fn record_match_ignores_unknown_id() {
+        let mut engine = init_test_similarity_engine();
+
+        // Should not panic: 404 isn't an id in the item set.
+        engine.record_match(404, 8, 0, 32.0);
+        engine.record_match(4, 404, 0, 32.0);
+    }
 }